@@ -4,8 +4,110 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 extern "C" {
     fn pulsedb_set(key_ptr: *const u8, key_len: usize, value_ptr: *const u8, value_len: usize);
-    fn pulsedb_get(key_ptr: *const u8, key_len: usize) -> *mut u8;
+    fn pulsedb_get_len(key_ptr: *const u8, key_len: usize) -> i64;
+    fn pulsedb_get_into(key_ptr: *const u8, key_len: usize, buf_ptr: *mut u8, buf_cap: usize) -> i64;
+    fn pulsedb_increment(key_ptr: *const u8, key_len: usize, delta: i64) -> i64;
+    fn pulsedb_generate_id(out_ptr: *mut u8) -> usize;
+    fn pulsedb_try_acquire(cost: i64) -> i32;
     fn pulsedb_log(level: i32, message_ptr: *const u8, message_len: usize);
+    fn pulsedb_metric_counter(name_ptr: *const u8, name_len: usize, delta: u64);
+    fn pulsedb_metric_gauge(name_ptr: *const u8, name_len: usize, value: f64);
+    fn pulsedb_metric_timing(name_ptr: *const u8, name_len: usize, start_ns: u64, end_ns: u64);
+    fn pulsedb_now_ns() -> u64;
+}
+
+// Host monotonic clock in nanoseconds, used to bracket timing spans.
+fn now_ns() -> u64 {
+    unsafe { pulsedb_now_ns() }
+}
+
+// Safe wrappers over the host metrics registry. Counters are monotonic and
+// unsigned, gauges are last-write-wins, and timings accumulate into buckets on
+// the host.
+mod metrics {
+    use super::{pulsedb_metric_counter, pulsedb_metric_gauge, pulsedb_metric_timing};
+
+    // Add `delta` to the monotonic counter `name`.
+    pub fn increment_counter(name: &str, delta: u64) {
+        unsafe { pulsedb_metric_counter(name.as_ptr(), name.len(), delta) }
+    }
+
+    // Set the gauge `name` to `value` (last write wins).
+    pub fn set_gauge(name: &str, value: f64) {
+        unsafe { pulsedb_metric_gauge(name.as_ptr(), name.len(), value) }
+    }
+
+    // Record a timing span for `name` given its start and end in nanoseconds.
+    pub fn record_timing(name: &str, start_ns: u64, end_ns: u64) {
+        unsafe { pulsedb_metric_timing(name.as_ptr(), name.len(), start_ns, end_ns) }
+    }
+}
+
+// ABI version this module is built against. The host keeps a registry of the
+// versions it supports and refuses to load a module whose `abi_version` it
+// does not recognise, turning a silent signature mismatch into a clean reject.
+// `pub` because the `pulsedb_plugin!` macro expands `$crate::ABI_VERSION` in
+// external plugin crates, where a private const would fail with E0603.
+pub const ABI_VERSION: i32 = 1;
+
+// Status codes returned by `init` so the host knows whether the handshake
+// succeeded before it starts dispatching events. `pub` for the same reason as
+// `ABI_VERSION` (referenced from macro-expanded plugin code).
+pub const INIT_OK: i32 = 0;
+
+// Exported so the host can negotiate the ABI before calling `handle_event`.
+#[wasm_bindgen]
+pub fn abi_version() -> i32 {
+    ABI_VERSION
+}
+
+/// Emit the four lifecycle exports (`abi_version`, `init`, `handle_event`,
+/// `cleanup`) with the exact extern signatures the host expects, so plugin
+/// authors only supply the bodies and can't get the boundary wrong.
+///
+/// Note: the original request asked for a `#[pulsedb_plugin]` *attribute*
+/// macro. A proc-macro attribute has to live in its own `proc-macro = true`
+/// crate, which this single example crate has no manifest for, so this ships
+/// as a function-like `macro_rules!` macro instead — same guarantee, invoked
+/// as `pulsedb_plugin! { .. }` rather than `#[pulsedb_plugin]`.
+///
+/// ```ignore
+/// pulsedb_plugin! {
+///     init: || { /* ... */ },
+///     handle_event: |event_type, key, value, timestamp| { /* ... */ },
+///     cleanup: || { /* ... */ },
+/// }
+/// ```
+#[macro_export]
+macro_rules! pulsedb_plugin {
+    (
+        init: $init:expr,
+        handle_event: $handle:expr,
+        cleanup: $cleanup:expr $(,)?
+    ) => {
+        #[wasm_bindgen]
+        pub fn abi_version() -> i32 {
+            $crate::ABI_VERSION
+        }
+
+        #[wasm_bindgen]
+        pub fn init() -> i32 {
+            let f: fn() -> i32 = $init;
+            f()
+        }
+
+        #[wasm_bindgen]
+        pub fn handle_event(event_type: &str, key: &str, value: &str, timestamp: i64) {
+            let f: fn(&str, &str, &str, i64) = $handle;
+            f(event_type, key, value, timestamp)
+        }
+
+        #[wasm_bindgen]
+        pub fn cleanup() {
+            let f: fn() = $cleanup;
+            f()
+        }
+    };
 }
 
 // Helper function to log messages
@@ -22,13 +124,100 @@ fn set_key(key: &str, value: &str) {
     }
 }
 
+// Read the value stored under `key`, returning `None` for a missing key.
+// We first ask the host for the length, allocate a guest buffer of exactly
+// that size, then have the host copy the bytes in. The buffer is owned by the
+// guest, so it is reclaimed when the returned `Vec` is dropped.
+//
+// Part of the read-side SDK surface plugin authors build on; this example's
+// counter goes through the host atomic (`increment_kv`) rather than a
+// read-modify-write, so `get`/`get_str` are unused here — hence `allow`.
+#[allow(dead_code)]
+fn get(key: &str) -> Option<Vec<u8>> {
+    let len = unsafe { pulsedb_get_len(key.as_ptr(), key.len()) };
+    if len < 0 {
+        return None;
+    }
+    let mut buf = vec![0u8; len as usize];
+    let written = unsafe { pulsedb_get_into(key.as_ptr(), key.len(), buf.as_mut_ptr(), buf.len()) };
+    if written < 0 {
+        return None;
+    }
+    buf.truncate(written as usize);
+    Some(buf)
+}
+
+// Convenience wrapper reading a value as a UTF-8 string. Part of the read-side
+// SDK surface (see `get`); unused in this example.
+#[allow(dead_code)]
+fn get_str(key: &str) -> Option<String> {
+    get(key).and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+// Atomically add `delta` to the numeric value stored under `key` and return
+// the new total. Backed by a per-key atomic counter on the host, so this is
+// safe to call from concurrent events without the lossy read-modify-write.
+// Named `increment_kv` to distinguish the KV-store counter from the metrics
+// registry counter in `metrics::increment_counter`.
+fn increment_kv(key: &str, delta: i64) -> i64 {
+    unsafe { pulsedb_increment(key.as_ptr(), key.len(), delta) }
+}
+
+// Generate a 128-bit, lexicographically sortable, collision-free ID (ULID)
+// and return it as a Crockford base32 string. The host writes the raw 16
+// bytes (48-bit millisecond timestamp + 80 bits of monotonic/random payload)
+// and guarantees strict ordering even within a single millisecond.
+fn generate_id() -> String {
+    let mut bytes = [0u8; 16];
+    let written = unsafe { pulsedb_generate_id(bytes.as_mut_ptr()) };
+    // A ULID is exactly 16 bytes; a short write would be silently mis-encoded
+    // as a zero-padded low-order value, so reject it rather than trust the host.
+    assert_eq!(written, 16, "pulsedb_generate_id must write 16 bytes");
+    encode_crockford(&bytes)
+}
+
+// Crockford base32 encoding of a 16-byte ULID into its canonical 26-char form.
+fn encode_crockford(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+    // Pack the bytes into a single 128-bit integer, then emit 26 base32 digits
+    // most-significant first (the top digit only carries the high 3 bits).
+    let mut value: u128 = 0;
+    for &b in bytes {
+        value = (value << 8) | b as u128;
+    }
+    let mut out = [0u8; 26];
+    for i in (0..26).rev() {
+        out[i] = ALPHABET[(value & 0x1f) as usize];
+        value >>= 5;
+    }
+    String::from_utf8(out.to_vec()).unwrap()
+}
+
+// Ask the host-side rate limiter for permission to perform an operation of
+// the given `cost`. Returns `true` if the tokens were available and consumed,
+// `false` if the bucket is empty and the caller should shed this load.
+fn rate_limited(cost: i64) -> bool {
+    unsafe { pulsedb_try_acquire(cost) != 0 }
+}
+
 // Main event handler function called by PulseDB
 #[wasm_bindgen]
 pub fn handle_event(event_type: &str, key: &str, value: &str, timestamp: i64) {
     log_info(&format!("Audit: {} {} = {} at {}", event_type, key, value, timestamp));
-    
-    // Create audit entry
-    let audit_key = format!("audit:{}:{}", timestamp, key);
+
+    // Shed load cheaply instead of issuing unbounded writes when the host
+    // rate limiter is saturated.
+    if !rate_limited(1) {
+        log_info("Audit entry dropped: rate limit exceeded");
+        return;
+    }
+
+    let start_ns = now_ns();
+
+    // Create audit entry. Use a monotonic ULID so entries on the same key
+    // within one millisecond no longer overwrite each other and still sort
+    // by time.
+    let audit_key = format!("audit:{}", generate_id());
     let audit_value = format!("{{\"type\":\"{}\",\"key\":\"{}\",\"value\":\"{}\",\"timestamp\":{}}}", 
                              event_type, key, value, timestamp);
     
@@ -37,15 +226,23 @@ pub fn handle_event(event_type: &str, key: &str, value: &str, timestamp: i64) {
     
     // Update audit counter
     let counter_key = format!("audit:count:{}", event_type);
-    // In a real implementation, we'd get the current count, increment it, and set it back
-    // For now, just log that we would increment it
-    log_info(&format!("Would increment counter: {}", counter_key));
+    let count = increment_kv(&counter_key, 1);
+    log_info(&format!("Counter {} = {}", counter_key, count));
+
+    // Emit metrics into the host registry instead of free-text logs.
+    metrics::increment_counter("audit.events.total", 1);
+    metrics::increment_counter(&format!("audit.events.{}", event_type), 1);
+    // Track the stored payload size and a per-event-type latency span.
+    metrics::set_gauge("audit.entry.bytes", audit_value.len() as f64);
+    metrics::record_timing(&format!("audit.events.{}.latency", event_type), start_ns, now_ns());
 }
 
-// Initialization function
+// Initialization function. Returns a status code so the host can abort the
+// handshake if setup fails.
 #[wasm_bindgen]
-pub fn init() {
+pub fn init() -> i32 {
     log_info("Audit logger WASM module initialized");
+    INIT_OK
 }
 
 // Cleanup function